@@ -1,8 +1,10 @@
 use eframe::egui;
-use std::{sync::{Arc, Mutex}, thread};
+use std::thread;
 
 const NUM_CHANNELS: usize = 8;
-const AUTOSAVE_SECONDS_INTERVAL: u64 = 60;
+const LOG_FLUSH_INTERVAL_SECONDS: u64 = 60;
+const SAMPLE_CHANNEL_CAPACITY: usize = 1024;
+const SERIAL_READ_TIMEOUT_MS: u64 = 100;
 
 #[derive(Clone)]
 struct Channel {
@@ -10,151 +12,497 @@ struct Channel {
     enabled: bool,
     colour: egui::Color32,
 }
+
+// One parsed line from the serial port, handed off to the UI and logging
+// threads over a channel instead of being written straight into shared state.
 #[derive(Clone)]
-struct ThermometerApp{
-    channels : Arc<Mutex< [Channel; NUM_CHANNELS] >>, // data from the serial port
-    timestamp_datetime: Arc<Mutex< Vec<(u64, String)> >>, // timestamp and equivalent datetime
-    port_names: Vec<String>, // list of available serial ports
-    selected_port_name: Arc<Mutex<String>>, // selected serial port
+struct Sample {
+    time: u64,
+    datetime: String,
+    values: [Option<f64>; NUM_CHANNELS],
 }
 
-impl ThermometerApp {
-    // TODO: Make safe from panics. This is called from a different thread so the application will just keep going.
-    fn save_to_csv(&self) {
-        // write the data to a .csv file
-        let current_time = chrono::Local::now().format("%Y-%m-%d %H-%M-%S").to_string();
+// Commands the UI sends to the serial thread to drive its connection state.
+enum SerialCommand {
+    Connect(String),
+    Disconnect,
+    Reconnect,
+    SetInputMode(InputMode),
+}
 
-        let mut writer = csv::Writer::from_path(format!("data {}.csv", current_time)).unwrap();
+// How incoming bytes are framed: newline-delimited ASCII CSV, or
+// COBS-framed `postcard` packets.
+#[derive(Clone, Copy, PartialEq)]
+enum InputMode {
+    Ascii,
+    Binary,
+}
 
-        let sensor_headers: Vec<String> = (1..=NUM_CHANNELS).map(|i| format!("Sensor {}", i)).collect();
+impl std::fmt::Display for InputMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputMode::Ascii => write!(f, "ASCII"),
+            InputMode::Binary => write!(f, "Binary (COBS + postcard)"),
+        }
+    }
+}
 
-        let mut headers = vec!["Time since start (ms)", "datetime of data"];
-        headers.extend(sensor_headers.iter().map(|s| s.as_str()));
-        writer.write_record(&headers).unwrap();
+// Wire format decoded from a COBS frame via `postcard`.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BinarySample {
+    timestamp: u64,
+    temps: [Option<f32>; NUM_CHANNELS],
+}
 
-        let channels: &[Channel; NUM_CHANNELS] = &self.channels.lock().unwrap();
-        let timestamp_datetime = &self.timestamp_datetime.lock().unwrap();
+// Connection state reported back by the serial thread, rendered by the UI.
+#[derive(Clone, PartialEq)]
+enum ConnectionStatus {
+    Disconnected,
+    Connected(String),
+    Error(String),
+}
 
-        for (i, (timestamp, datetime)) in timestamp_datetime.iter().enumerate() {
-            let mut record = vec![timestamp.to_string(), datetime.to_string()];
-            for channel in channels {
-                let tempr: String = channel.data[i].1.map(|t| t.to_string()).unwrap_or_else(String::new);
-                record.push(tempr)
-            }
-            writer.write_record(&record).unwrap();
+impl std::fmt::Display for ConnectionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionStatus::Disconnected => write!(f, "Disconnected"),
+            ConnectionStatus::Connected(port) => write!(f, "Connected to {port}"),
+            ConnectionStatus::Error(err) => write!(f, "Error: {err}"),
         }
-        
-        writer.flush().unwrap();
-        println!("Data saved to .CSV file");
     }
-    
-    // TODO: Make safe from panics. This is called from a different thread so the application will just keep going.
-    fn read_input_from_serial(&self) {
-        println!("Available serial ports: {:?}", self.port_names);
+}
 
-        loop {
-            println!("self.selected_port_name: {:?}", self.selected_port_name);
-            if self.selected_port_name.lock().unwrap().is_empty() {
-                std::thread::sleep(std::time::Duration::from_secs(1));
-            }
-            else{
-                break;
+// Commands the UI sends to the logging thread to start/stop continuous logging.
+enum LoggingCommand {
+    Start { directory: String, gzip: bool },
+    Stop,
+}
+
+// Logging state reported back by the logging thread, rendered by the UI.
+#[derive(Clone, PartialEq)]
+enum LoggingStatus {
+    Stopped,
+    Logging(String),
+    Error(String),
+}
+
+impl std::fmt::Display for LoggingStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoggingStatus::Stopped => write!(f, "Not logging"),
+            LoggingStatus::Logging(path) => write!(f, "Logging to {path}"),
+            LoggingStatus::Error(err) => write!(f, "Error: {err}"),
+        }
+    }
+}
+
+struct ThermometerApp{
+    channels : [Channel; NUM_CHANNELS], // data accumulated so far, owned by the UI thread
+    timestamp_datetime: Vec<(u64, String)>, // timestamp and equivalent datetime
+    port_names: Vec<String>, // list of available serial ports
+    selected_port_name: String, // port currently selected in the ComboBox
+    connection_status: ConnectionStatus, // latest status reported by the serial thread
+    input_mode: InputMode, // ASCII CSV vs COBS + postcard framing, mirrored to the serial thread
+    decode_errors: std::sync::Arc<std::sync::atomic::AtomicU64>, // frames that failed to decode in binary mode
+    log_directory: String, // output directory for continuous logging, shown in a text field
+    gzip_logging: bool, // whether continuous logging gzip-compresses the output file
+    logging_status: LoggingStatus, // latest status reported by the logging thread
+    dropped_log_rows: std::sync::Arc<std::sync::atomic::AtomicU64>, // rows dropped because the logging channel was full
+    rolling_window_enabled: bool, // show only the last `rolling_window_seconds` of data
+    rolling_window_seconds: f64, // length of the rolling window, picked with a slider
+    auto_follow: bool, // keep the rolling window pinned to the latest sample vs. free pan/zoom
+    sample_rx: crossbeam_channel::Receiver<Sample>, // new samples from the serial thread
+    status_rx: crossbeam_channel::Receiver<ConnectionStatus>, // connection state updates
+    logging_status_rx: crossbeam_channel::Receiver<LoggingStatus>, // logging state updates
+    command_tx: crossbeam_channel::Sender<SerialCommand>, // connect/disconnect requests
+    logging_command_tx: crossbeam_channel::Sender<LoggingCommand>, // start/stop logging requests
+}
+
+impl ThermometerApp {
+    // Drain every sample and status update the serial and logging threads have
+    // pushed since the last frame and apply them to the UI-owned state. Never blocks.
+    fn drain_channel_messages(&mut self) {
+        while let Ok(sample) = self.sample_rx.try_recv() {
+            for (channel, value) in self.channels.iter_mut().zip(sample.values) {
+                channel.data.push((sample.time, value));
             }
+            self.timestamp_datetime.push((sample.time, sample.datetime));
         }
 
-        let port_name = self.selected_port_name.lock().unwrap().clone();
+        while let Ok(status) = self.status_rx.try_recv() {
+            self.connection_status = status;
+        }
 
-        let mut port = serialport::new(port_name, 9600)
-            .timeout(std::time::Duration::from_secs(1))
-            .open()
-            .expect("Failed to open serial port");
+        while let Ok(status) = self.logging_status_rx.try_recv() {
+            self.logging_status = status;
+        }
+    }
 
+    // Owns the serial port for as long as it's connected and acts as a small
+    // connect/disconnect/reconnect state machine driven by `command_rx`.
+    // TODO: Make safe from panics. This is called from a different thread so the application will just keep going.
+    fn read_input_from_serial(
+        command_rx: crossbeam_channel::Receiver<SerialCommand>,
+        ui_tx: crossbeam_channel::Sender<Sample>,
+        logging_tx: crossbeam_channel::Sender<Sample>,
+        status_tx: crossbeam_channel::Sender<ConnectionStatus>,
+        decode_errors: std::sync::Arc<std::sync::atomic::AtomicU64>,
+        dropped_log_rows: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    ) {
+        let mut port: Option<Box<dyn serialport::SerialPort>> = None;
+        let mut port_name: Option<String> = None;
+        let mut input_mode = InputMode::Ascii;
         let mut serial_buf = String::new();
+        let mut binary_buf: Vec<u8> = Vec::new();
 
         loop {
+            match command_rx.try_recv() {
+                Ok(SerialCommand::Connect(name)) => {
+                    serial_buf.clear();
+                    binary_buf.clear();
+                    match serialport::new(&name, 9600)
+                        .timeout(std::time::Duration::from_millis(SERIAL_READ_TIMEOUT_MS))
+                        .open()
+                    {
+                        Ok(opened) => {
+                            port = Some(opened);
+                            port_name = Some(name.clone());
+                            let _ = status_tx.send(ConnectionStatus::Connected(name));
+                        }
+                        Err(e) => {
+                            port = None;
+                            // Remember the port even on a failed first connect so
+                            // a later `Reconnect` has something to retry.
+                            port_name = Some(name);
+                            let _ = status_tx.send(ConnectionStatus::Error(e.to_string()));
+                        }
+                    }
+                }
+                Ok(SerialCommand::Disconnect) => {
+                    port = None;
+                    serial_buf.clear();
+                    binary_buf.clear();
+                    let _ = status_tx.send(ConnectionStatus::Disconnected);
+                }
+                Ok(SerialCommand::Reconnect) => {
+                    if let Some(name) = port_name.clone() {
+                        serial_buf.clear();
+                        binary_buf.clear();
+                        match serialport::new(&name, 9600)
+                            .timeout(std::time::Duration::from_millis(SERIAL_READ_TIMEOUT_MS))
+                            .open()
+                        {
+                            Ok(opened) => {
+                                port = Some(opened);
+                                let _ = status_tx.send(ConnectionStatus::Connected(name));
+                            }
+                            Err(e) => {
+                                port = None;
+                                let _ = status_tx.send(ConnectionStatus::Error(e.to_string()));
+                            }
+                        }
+                    }
+                }
+                Ok(SerialCommand::SetInputMode(mode)) => {
+                    input_mode = mode;
+                    serial_buf.clear();
+                    binary_buf.clear();
+                }
+                Err(crossbeam_channel::TryRecvError::Disconnected) => return, // UI has shut down
+                Err(crossbeam_channel::TryRecvError::Empty) => (),
+            }
+
+            let Some(active_port) = port.as_mut() else {
+                std::thread::sleep(std::time::Duration::from_millis(SERIAL_READ_TIMEOUT_MS));
+                continue;
+            };
+
             let mut buf: Vec<u8> = vec![0; 100];
-            match port.read(buf.as_mut_slice()) {
+            match active_port.read(buf.as_mut_slice()) {
                 Ok(t) => {
                     if t == 0 {
                         continue;
                     }
-                    let s = String::from_utf8_lossy(&buf[..t]);
-                    serial_buf.push_str(&s);
-                    while let Some(pos) = serial_buf.find("\r") {
-                        let line = serial_buf[..pos].to_string();
-                        self.append_data(&line);
-                        serial_buf = serial_buf[pos + 1..].to_string();
+
+                    let samples = match input_mode {
+                        InputMode::Ascii => {
+                            let s = String::from_utf8_lossy(&buf[..t]);
+                            serial_buf.push_str(&s);
+                            let mut samples = Vec::new();
+                            while let Some(pos) = serial_buf.find("\r") {
+                                let line = serial_buf[..pos].to_string();
+                                samples.extend(Self::parse_line(&line));
+                                serial_buf = serial_buf[pos + 1..].to_string();
+                            }
+                            samples
+                        }
+                        InputMode::Binary => {
+                            binary_buf.extend_from_slice(&buf[..t]);
+                            let mut samples = Vec::new();
+                            // 0x00 is the COBS frame delimiter: each non-empty
+                            // segment up to it is one encoded frame, and the next
+                            // 0x00 re-syncs framing even if we started mid-stream.
+                            while let Some(pos) = binary_buf.iter().position(|&b| b == 0) {
+                                let frame: Vec<u8> = binary_buf.drain(..=pos).collect();
+                                if frame.len() > 1 {
+                                    samples.extend(Self::decode_binary_frame(&frame, &decode_errors));
+                                }
+                            }
+                            samples
+                        }
+                    };
+
+                    for sample in samples {
+                        // Best-effort delivery: if a consumer is lagging behind,
+                        // drop the sample rather than block the reader.
+                        match ui_tx.try_send(sample.clone()) {
+                            Ok(()) | Err(crossbeam_channel::TrySendError::Full(_)) => (),
+                            Err(crossbeam_channel::TrySendError::Disconnected(_)) => return,
+                        }
+                        match logging_tx.try_send(sample) {
+                            Ok(()) => (),
+                            Err(crossbeam_channel::TrySendError::Full(_)) => {
+                                // Unlike the UI channel, losing a logged row is a gap
+                                // in the recorded data, so it must be counted, not silent.
+                                dropped_log_rows.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                eprintln!("Logging channel full, dropped a sample");
+                            }
+                            Err(crossbeam_channel::TrySendError::Disconnected(_)) => return,
+                        }
                     }
                 }
                 Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => (),
-                Err(e) => eprintln!("Error reading from serial port: {}", e),
+                Err(e) => {
+                    eprintln!("Error reading from serial port: {}", e);
+                    port = None;
+                    let _ = status_tx.send(ConnectionStatus::Error(e.to_string()));
+                }
             }
         }
-
     }
 
-    fn append_data (&self, new_data: &str) {
-        // first check if str is an info string
-        let first_char = new_data.chars().next().unwrap();
+    // Parses one CSV line (`time,12.3C,...`) into a `Sample`, or handles it as
+    // an info string and returns `None`.
+    fn parse_line(new_data: &str) -> Option<Sample> {
+        let first_char = new_data.chars().next()?;
         if ['#', '?', '/', '-'].contains(&first_char) {
-            self.handle_info_string(new_data);
-            return;
+            Self::handle_info_string(new_data);
+            return None;
         }
 
-        // split the incoming data by commas
         let mut split_data = new_data.split(',');
 
-        let time = split_data.next().unwrap().parse::<u64>().unwrap();
-        let datetime_received = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+        let time = split_data.next()?.parse::<u64>().ok()?;
+        let datetime = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
 
-        let mut channels = self.channels.lock().unwrap();
-        for (channel, data_str) in channels.iter_mut().zip(split_data.take(NUM_CHANNELS)) {
+        let mut values = [None; NUM_CHANNELS];
+        for (value, data_str) in values.iter_mut().zip(split_data.take(NUM_CHANNELS)) {
             if data_str.is_empty() {
-                channel.data.push((time, None));
                 continue;
             }
             // convert the data to f64 while removing the last character (which is C for celsius)
-            let value = data_str.trim_end_matches('C').parse::<f64>().unwrap();
-            channel.data.push((time, Some(value)));
+            // a malformed reading is dropped rather than panicking the reader thread
+            *value = data_str.trim_end_matches('C').parse::<f64>().ok();
         }
 
-        let mut timestamp_to_datetime = self.timestamp_datetime.lock().unwrap();
-        timestamp_to_datetime.push((time, datetime_received));
+        Some(Sample { time, datetime, values })
     }
 
-
-    fn handle_info_string(&self, info_string: &str) {
+    fn handle_info_string(info_string: &str) {
         // temporary print statement for testing
         println!("Info string received: {}", info_string);
     }
+
+    // Decodes one COBS-framed, zero-terminated chunk into a `Sample` in a
+    // single step with `postcard::from_bytes_cobs`, which removes the byte
+    // stuffing in place and deserializes the zero-free payload. Counts rather
+    // than panics on failure, since a corrupted frame shouldn't take down the
+    // reader thread.
+    fn decode_binary_frame(frame: &[u8], decode_errors: &std::sync::Arc<std::sync::atomic::AtomicU64>) -> Option<Sample> {
+        let mut decode_buf = frame.to_vec();
+        match postcard::from_bytes_cobs::<BinarySample>(&mut decode_buf) {
+            Ok(binary_sample) => Some(Sample {
+                time: binary_sample.timestamp,
+                datetime: chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                values: binary_sample.temps.map(|t| t.map(|v| v as f64)),
+            }),
+            Err(_) => {
+                decode_errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                None
+            }
+        }
+    }
 }
 
+// Writes the given data to a timestamped .csv file. Free function (rather
+// than a method) so the "Save snapshot" button can hand off a snapshot of the
+// UI-owned vectors to a background thread without cloning the whole app.
+// TODO: Make safe from panics. This is called from a different thread so the application will just keep going.
+fn save_to_csv(channels: &[Channel; NUM_CHANNELS], timestamp_datetime: &[(u64, String)]) {
+    let current_time = chrono::Local::now().format("%Y-%m-%d %H-%M-%S").to_string();
+
+    let mut writer = csv::Writer::from_path(format!("data {}.csv", current_time)).unwrap();
+    writer.write_record(&csv_headers()).unwrap();
+
+    for (i, (timestamp, datetime)) in timestamp_datetime.iter().enumerate() {
+        let mut record = vec![timestamp.to_string(), datetime.to_string()];
+        for channel in channels {
+            let tempr: String = channel.data[i].1.map(|t| t.to_string()).unwrap_or_else(String::new);
+            record.push(tempr)
+        }
+        writer.write_record(&record).unwrap();
+    }
+
+    writer.flush().unwrap();
+    println!("Data saved to .CSV file");
+}
+
+// Headers shared by the snapshot writer above and the streaming log writer below.
+fn csv_headers() -> Vec<String> {
+    let mut headers = vec!["Time since start (ms)".to_string(), "datetime of data".to_string()];
+    headers.extend((1..=NUM_CHANNELS).map(|i| format!("Sensor {}", i)));
+    headers
+}
+
+// Opens a new continuous-logging file in `directory`, optionally gzip-compressed,
+// writes its header row, and returns the writer along with the path it opened.
+fn open_log_writer(directory: &str, gzip: bool) -> std::io::Result<(csv::Writer<Box<dyn std::io::Write + Send>>, String)> {
+    let current_time = chrono::Local::now().format("%Y-%m-%d %H-%M-%S").to_string();
+    let file_name = if gzip { format!("data {current_time}.csv.gz") } else { format!("data {current_time}.csv") };
+    let path = std::path::Path::new(directory).join(file_name);
+
+    let file = std::fs::File::create(&path)?;
+    let sink: Box<dyn std::io::Write + Send> = if gzip {
+        Box::new(flate2::write::GzEncoder::new(file, flate2::Compression::default()))
+    } else {
+        Box::new(file)
+    };
+
+    let mut writer = csv::Writer::from_writer(sink);
+    writer.write_record(&csv_headers())?;
+    Ok((writer, path.display().to_string()))
+}
+
+// Appends one sample as a single row to an already-open log writer.
+fn write_log_row(writer: &mut csv::Writer<Box<dyn std::io::Write + Send>>, sample: &Sample) -> std::io::Result<()> {
+    let mut record = vec![sample.time.to_string(), sample.datetime.clone()];
+    record.extend(sample.values.iter().map(|v| v.map(|t| t.to_string()).unwrap_or_default()));
+    writer.write_record(&record)?;
+    Ok(())
+}
+
+// Downsamples `data` to at most two points (a minimum and a maximum) per
+// pixel-wide bucket across `[x_min, x_max]`, so spikes survive decimation
+// instead of being skipped over the way a fixed stride would.
+fn downsample_min_max(data: &[(u64, Option<f64>)], x_min: f64, x_max: f64, num_buckets: usize) -> Vec<[f64; 2]> {
+    let points: Vec<(f64, f64)> = data.iter().filter_map(|&(time, temp)| temp.map(|t| (time as f64, t))).collect();
+
+    if num_buckets == 0 || x_max <= x_min {
+        return points.into_iter().map(|(x, y)| [x, y]).collect();
+    }
+
+    let bucket_width = (x_max - x_min) / num_buckets as f64;
+    let mut buckets: Vec<Option<((f64, f64), (f64, f64))>> = vec![None; num_buckets]; // (min point, max point)
+
+    for (x, y) in points {
+        if x < x_min || x > x_max {
+            continue;
+        }
+        let bucket = (((x - x_min) / bucket_width) as usize).min(num_buckets - 1);
+        match &mut buckets[bucket] {
+            None => buckets[bucket] = Some(((x, y), (x, y))),
+            Some((min_point, max_point)) => {
+                if y < min_point.1 { *min_point = (x, y); }
+                if y > max_point.1 { *max_point = (x, y); }
+            }
+        }
+    }
+
+    let mut plotted = Vec::with_capacity(num_buckets * 2);
+    for (min_point, max_point) in buckets.into_iter().flatten() {
+        // Emit in time order so the line doesn't zigzag backwards within a bucket.
+        let (first, second) = if min_point.0 <= max_point.0 { (min_point, max_point) } else { (max_point, min_point) };
+        plotted.push([first.0, first.1]);
+        if first != second {
+            plotted.push([second.0, second.1]);
+        }
+    }
+    plotted
+}
 
 impl eframe::App for ThermometerApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         ctx.request_repaint(); // Request regular updates for real-time changes
-        
+
+        self.drain_channel_messages();
+
         // Create the UI
         egui::CentralPanel::default().show(ctx, |ui| {
 
-        
-            let selected_port_name = self.selected_port_name.lock().unwrap().clone();
-            egui::ComboBox::from_label("Select the serial port to read data from")
-                .selected_text(&selected_port_name)
-                .show_ui(ui, |ui| {
-                    for port in &self.port_names {
-                        if ui.selectable_label(selected_port_name == *port, port).clicked() {
-                            *self.selected_port_name.lock().unwrap() = port.clone();
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Select the serial port to read data from")
+                    .selected_text(&self.selected_port_name)
+                    .show_ui(ui, |ui| {
+                        for port in &self.port_names {
+                            if ui.selectable_label(self.selected_port_name == *port, port).clicked() {
+                                self.selected_port_name = port.clone();
+                                // Switching ports while already connected tears down
+                                // the old handle and opens the new one immediately.
+                                if matches!(self.connection_status, ConnectionStatus::Connected(_)) {
+                                    let _ = self.command_tx.send(SerialCommand::Connect(self.selected_port_name.clone()));
+                                }
+                            }
+                        }
+                    });
+
+                match self.connection_status {
+                    ConnectionStatus::Connected(_) => {
+                        if ui.button("Disconnect").clicked() {
+                            let _ = self.command_tx.send(SerialCommand::Disconnect);
                         }
                     }
-                });
-    
+                    ConnectionStatus::Disconnected => {
+                        let can_connect = !self.selected_port_name.is_empty();
+                        if ui.add_enabled(can_connect, egui::Button::new("Connect")).clicked() {
+                            let _ = self.command_tx.send(SerialCommand::Connect(self.selected_port_name.clone()));
+                        }
+                    }
+                    ConnectionStatus::Error(_) => {
+                        // Retry the port that just failed instead of making the user
+                        // re-pick it from the ComboBox.
+                        if ui.button("Reconnect").clicked() {
+                            let _ = self.command_tx.send(SerialCommand::Reconnect);
+                        }
+                    }
+                }
+
+                ui.label(self.connection_status.to_string());
+
+                ui.separator();
+
+                egui::ComboBox::from_label("Input format")
+                    .selected_text(self.input_mode.to_string())
+                    .show_ui(ui, |ui| {
+                        for mode in [InputMode::Ascii, InputMode::Binary] {
+                            if ui.selectable_label(self.input_mode == mode, mode.to_string()).clicked() {
+                                self.input_mode = mode;
+                                let _ = self.command_tx.send(SerialCommand::SetInputMode(mode));
+                            }
+                        }
+                    });
+
+                if self.input_mode == InputMode::Binary {
+                    let errors = self.decode_errors.load(std::sync::atomic::Ordering::Relaxed);
+                    ui.label(format!("Decode errors: {errors}"));
+                }
+            });
+
             ui.heading("Current Temperature Data");
-            
+
             //get window size
             let window_size = ui.available_size_before_wrap();
 
-            let channels: &mut [Channel; NUM_CHANNELS] = &mut self.channels.lock().unwrap();
+            let channels = &mut self.channels;
             // plot grid of values (2x4)
             egui::Grid::new("current_data_grid").show(ui, |ui| {
                 const NUM_COLS: usize = 4;
@@ -169,8 +517,8 @@ impl eframe::App for ThermometerApp {
                                 Some(Some(f)) => ui.label(egui::RichText::new(format!("{:6.3}°C", f)).strong()),
                                 _ => ui.label("No data"),
                             }
-                        });    
-                        
+                        });
+
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             ui.checkbox(enabled, "");
                             ui.color_edit_button_srgba(colour);
@@ -181,33 +529,106 @@ impl eframe::App for ThermometerApp {
                     }
                 }
             });
-            
-            // Save data to .CSV file on button press
-            if ui.button("Save Data").on_hover_text("Save the current data to a .CSV file (YMD HMS for alphabetical sorting)").clicked() {
-                let save_thread = self.clone();
+
+            // Save a full snapshot of the current data to a new .CSV file on button press
+            if ui.button("Save snapshot").on_hover_text("Save the current data to a .CSV file (YMD HMS for alphabetical sorting)").clicked() {
+                let channels = self.channels.clone();
+                let timestamp_datetime = self.timestamp_datetime.clone();
                 thread::spawn(move || {
-                    save_thread.save_to_csv();
+                    save_to_csv(&channels, &timestamp_datetime);
                 });
             }
 
+            ui.horizontal(|ui| {
+                let is_logging = matches!(self.logging_status, LoggingStatus::Logging(_));
+                ui.add_enabled(!is_logging, egui::TextEdit::singleline(&mut self.log_directory))
+                    .on_hover_text("Output directory for continuous logging");
+                ui.add_enabled(!is_logging, egui::Checkbox::new(&mut self.gzip_logging, "Compress with gzip"));
+
+                if is_logging {
+                    if ui.button("Stop logging").clicked() {
+                        let _ = self.logging_command_tx.send(LoggingCommand::Stop);
+                    }
+                } else if ui.button("Start logging").clicked() {
+                    let _ = self.logging_command_tx.send(LoggingCommand::Start {
+                        directory: self.log_directory.clone(),
+                        gzip: self.gzip_logging,
+                    });
+                }
+
+                ui.label(self.logging_status.to_string());
+
+                let dropped = self.dropped_log_rows.load(std::sync::atomic::Ordering::Relaxed);
+                ui.label(format!("Dropped log rows: {dropped}"));
+            });
+
             ui.separator();
 
             ui.heading("Temperature Data Plot");
 
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.rolling_window_enabled, "Rolling window");
+                ui.add_enabled(
+                    self.rolling_window_enabled,
+                    egui::Slider::new(&mut self.rolling_window_seconds, 1.0..=600.0).text("Window (s)"),
+                );
+                ui.add_enabled(self.rolling_window_enabled, egui::Checkbox::new(&mut self.auto_follow, "Auto-follow"));
+            });
+
+            // Latest timestamp and overall time extent across the enabled channels,
+            // used to pick the visible x-range before we know the plot's own bounds.
+            let latest_time_ms = channels.iter()
+                .filter(|c| c.enabled)
+                .filter_map(|c| c.data.last().map(|&(time, _)| time as f64))
+                .fold(0.0_f64, f64::max);
+
+            let data_extent = channels.iter()
+                .filter(|c| c.enabled)
+                .filter_map(|c| Some((c.data.first()?.0 as f64, c.data.last()?.0 as f64)))
+                .fold(None, |acc: Option<(f64, f64)>, (first, last)| match acc {
+                    None => Some((first, last)),
+                    Some((min_x, max_x)) => Some((min_x.min(first), max_x.max(last))),
+                });
+
+            let (x_min, x_max) = if self.rolling_window_enabled {
+                let window_ms = self.rolling_window_seconds * 1000.0;
+                ((latest_time_ms - window_ms).max(0.0), latest_time_ms)
+            } else {
+                data_extent.unwrap_or((0.0, 1.0))
+            };
+
+            let num_buckets = window_size.x.round().max(1.0) as usize;
+            let auto_follow = self.rolling_window_enabled && self.auto_follow;
+
             let plot = egui_plot::Plot::new("data_plot");
             plot.show(ui, |plot_ui| {
-                for Channel{ enabled, colour, data} in channels.iter() {
-                    if *enabled {
-                        // Filter out times with `None` temps
-                        let all_points: Vec<[f64;2]> = data.iter().filter_map(|&(time, opt_temp)| opt_temp.map(|t| [time as f64, t])).collect();
+                let mut y_min = f64::INFINITY;
+                let mut y_max = f64::NEG_INFINITY;
 
-                        // If we have many values only plot some of them
-                        let points_per_pixel = (all_points.len() as f32 / window_size.x+0.01).round() as usize;
-                        let plotted_points = if points_per_pixel < 2 { all_points }
-                        else { all_points.into_iter().step_by(points_per_pixel).collect() };
+                for Channel{ enabled, colour, data} in channels.iter() {
+                    if !*enabled {
+                        continue;
+                    }
 
-                        plot_ui.line(egui_plot::Line::new(egui_plot::PlotPoints::from(plotted_points)).color(*colour));
+                    let plotted_points = downsample_min_max(data, x_min, x_max, num_buckets);
+                    for point in &plotted_points {
+                        y_min = y_min.min(point[1]);
+                        y_max = y_max.max(point[1]);
                     }
+
+                    plot_ui.line(egui_plot::Line::new(egui_plot::PlotPoints::from(plotted_points)).color(*colour));
+                }
+
+                // Auto-follow pins the view to the rolling window; otherwise leave
+                // the bounds alone so free pan/zoom keeps the user's chosen view.
+                if auto_follow {
+                    let (y_min, y_max) = if y_min.is_finite() && y_max.is_finite() {
+                        let padding = ((y_max - y_min) * 0.05).max(0.5);
+                        (y_min - padding, y_max + padding)
+                    } else {
+                        (-1.0, 1.0)
+                    };
+                    plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max([x_min, y_min], [x_max, y_max]));
                 }
             });
         });
@@ -228,13 +649,13 @@ fn main() {
     ];
 
     const NUM_EXAMPLES: usize = 100_000;
-    let channels: [Channel; NUM_CHANNELS] = std::array::from_fn(|i| Channel{ 
+    let channels: [Channel; NUM_CHANNELS] = std::array::from_fn(|i| Channel{
         // Some dummy data for testing lots of points
-        data: std::array::from_fn::<_, NUM_EXAMPLES,_>( |j| 
+        data: std::array::from_fn::<_, NUM_EXAMPLES,_>( |j|
             (j as u64, Some(f64::sin(j as f64 / 3000.0 + (i*20) as f64) // Nice sine wave example, each channel offset by 20 radians
-         ))).to_vec(), 
-        enabled: true, 
-        colour: DEFAULT_LINE_COLOURS[i] }); 
+         ))).to_vec(),
+        enabled: true,
+        colour: DEFAULT_LINE_COLOURS[i] });
 
     let timestamp_examples = std::iter::repeat_n((0, String::from("ABCD")), NUM_EXAMPLES).collect();
 
@@ -244,27 +665,97 @@ fn main() {
         port_names.push(port.port_name);
     }
 
+    let (ui_tx, ui_rx) = crossbeam_channel::bounded::<Sample>(SAMPLE_CHANNEL_CAPACITY);
+    let (logging_tx, logging_rx) = crossbeam_channel::bounded::<Sample>(SAMPLE_CHANNEL_CAPACITY);
+    let (status_tx, status_rx) = crossbeam_channel::unbounded::<ConnectionStatus>();
+    let (command_tx, command_rx) = crossbeam_channel::unbounded::<SerialCommand>();
+    let (logging_status_tx, logging_status_rx) = crossbeam_channel::unbounded::<LoggingStatus>();
+    let (logging_command_tx, logging_command_rx) = crossbeam_channel::unbounded::<LoggingCommand>();
+
+    let decode_errors = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let dropped_log_rows = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+
     let app = ThermometerApp {
-        channels: Arc::new(Mutex::new(channels)),
-        timestamp_datetime: Arc::new(Mutex::new(timestamp_examples)),
+        channels,
+        timestamp_datetime: timestamp_examples,
         port_names,
-        selected_port_name: Arc::new(Mutex::new("".to_string())),
+        selected_port_name: String::new(),
+        connection_status: ConnectionStatus::Disconnected,
+        input_mode: InputMode::Ascii,
+        decode_errors: decode_errors.clone(),
+        log_directory: ".".to_string(),
+        gzip_logging: false,
+        logging_status: LoggingStatus::Stopped,
+        dropped_log_rows: dropped_log_rows.clone(),
+        rolling_window_enabled: true,
+        rolling_window_seconds: 60.0,
+        auto_follow: true,
+        sample_rx: ui_rx,
+        status_rx,
+        logging_status_rx,
+        command_tx,
+        logging_command_tx,
     };
 
-    // thread to add data
-    let app_read_in = app.clone();
+    // thread to read data from the serial port and fan it out to the UI and logging threads
     thread::spawn(move || {
-        // app_read_in.read_input_from_cmd();
-        app_read_in.read_input_from_serial();
+        ThermometerApp::read_input_from_serial(
+            command_rx,
+            ui_tx,
+            logging_tx,
+            status_tx,
+            decode_errors,
+            dropped_log_rows,
+        );
     });
 
-    // thread to autosave data
-    let app_autosave = app.clone();
+    // thread to log data: appends each sample handed to it over `logging_rx` to an
+    // open writer as it arrives, rather than rewriting the whole dataset, so I/O
+    // per sample is O(1) regardless of how much history has already been recorded.
     thread::spawn(move || {
+        let mut writer: Option<csv::Writer<Box<dyn std::io::Write + Send>>> = None;
+        let ticks = crossbeam_channel::tick(std::time::Duration::from_secs(LOG_FLUSH_INTERVAL_SECONDS));
+
         loop {
-            std::thread::sleep(std::time::Duration::from_secs(AUTOSAVE_SECONDS_INTERVAL));
-            println!("Autosaving data...");
-            app_autosave.save_to_csv();
+            crossbeam_channel::select! {
+                recv(logging_rx) -> sample => {
+                    if let Ok(sample) = sample {
+                        if let Some(csv_writer) = writer.as_mut() {
+                            if let Err(e) = write_log_row(csv_writer, &sample) {
+                                eprintln!("Error writing log row: {e}");
+                            }
+                        }
+                    }
+                }
+                recv(logging_command_rx) -> command => {
+                    match command {
+                        Ok(LoggingCommand::Start { directory, gzip }) => {
+                            match open_log_writer(&directory, gzip) {
+                                Ok((csv_writer, path)) => {
+                                    writer = Some(csv_writer);
+                                    let _ = logging_status_tx.send(LoggingStatus::Logging(path));
+                                }
+                                Err(e) => {
+                                    let _ = logging_status_tx.send(LoggingStatus::Error(e.to_string()));
+                                }
+                            }
+                        }
+                        Ok(LoggingCommand::Stop) => {
+                            if let Some(mut csv_writer) = writer.take() {
+                                let _ = csv_writer.flush();
+                            }
+                            let _ = logging_status_tx.send(LoggingStatus::Stopped);
+                        }
+                        Err(_) => return, // UI has shut down
+                    }
+                }
+                recv(ticks) -> _ => {
+                    if let Some(csv_writer) = writer.as_mut() {
+                        println!("Flushing log file...");
+                        let _ = csv_writer.flush();
+                    }
+                }
+            }
         }
     });
 
@@ -275,6 +766,87 @@ fn main() {
             ..Default::default()
         },
         Box::new(|_cc|{
-            Ok(Box::<ThermometerApp>::from(app))
+            Ok(Box::new(app))
         })).unwrap();
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downsample_min_max_keeps_spikes_per_bucket() {
+        let data: Vec<(u64, Option<f64>)> = vec![
+            (0, Some(0.0)),
+            (1, Some(100.0)), // spike
+            (2, Some(0.0)),
+            (3, Some(-100.0)), // spike
+            (4, Some(0.0)),
+        ];
+
+        let plotted = downsample_min_max(&data, 0.0, 4.0, 1);
+
+        let max_y = plotted.iter().map(|p| p[1]).fold(f64::NEG_INFINITY, f64::max);
+        let min_y = plotted.iter().map(|p| p[1]).fold(f64::INFINITY, f64::min);
+        assert_eq!(max_y, 100.0);
+        assert_eq!(min_y, -100.0);
+    }
+
+    #[test]
+    fn downsample_min_max_falls_back_to_raw_points_on_degenerate_range() {
+        let data = vec![(0, Some(1.0)), (1, Some(2.0))];
+        let plotted = downsample_min_max(&data, 5.0, 5.0, 10);
+        assert_eq!(plotted, vec![[0.0, 1.0], [1.0, 2.0]]);
+    }
+
+    #[test]
+    fn downsample_min_max_skips_missing_samples() {
+        let data = vec![(0, None), (1, Some(3.0))];
+        let plotted = downsample_min_max(&data, 0.0, 1.0, 1);
+        assert_eq!(plotted, vec![[1.0, 3.0]]);
+    }
+
+    #[test]
+    fn decode_binary_frame_round_trips_a_valid_packet() {
+        let mut temps = [None; NUM_CHANNELS];
+        temps[0] = Some(1.5);
+        temps[2] = Some(-2.5);
+        let sample = BinarySample { timestamp: 42, temps };
+        let encoded = postcard::to_stdvec_cobs(&sample).unwrap();
+
+        let decode_errors = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let decoded = ThermometerApp::decode_binary_frame(&encoded, &decode_errors).unwrap();
+
+        assert_eq!(decoded.time, 42);
+        assert_eq!(decoded.values[0], Some(1.5));
+        assert_eq!(decoded.values[1], None);
+        assert_eq!(decoded.values[2], Some(-2.5));
+        assert_eq!(decode_errors.load(std::sync::atomic::Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn decode_binary_frame_counts_errors_on_garbage() {
+        let decode_errors = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let garbage = [0xFF, 0xFF, 0xFF, 0x00];
+
+        let decoded = ThermometerApp::decode_binary_frame(&garbage, &decode_errors);
+
+        assert!(decoded.is_none());
+        assert_eq!(decode_errors.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn parse_line_rejects_empty_and_malformed_lines() {
+        assert!(ThermometerApp::parse_line("").is_none());
+        assert!(ThermometerApp::parse_line("not-a-number,1.0C").is_none());
+    }
+
+    #[test]
+    fn parse_line_parses_a_valid_csv_line() {
+        let sample = ThermometerApp::parse_line("100,12.3C,,45.6C").unwrap();
+        assert_eq!(sample.time, 100);
+        assert_eq!(sample.values[0], Some(12.3));
+        assert_eq!(sample.values[1], None);
+        assert_eq!(sample.values[2], Some(45.6));
+    }
+}